@@ -0,0 +1,154 @@
+//! Optional argument dump/replay subsystem for launched kernels.
+//!
+//! When enabled, the caller passes a launch's device buffers to [`dump_pre`]
+//! immediately before the launch and to [`dump_post`] immediately after it;
+//! each buffer is copied back to the host and written to disk, and
+//! `dump_pre` also writes a manifest describing the launch configuration.
+//! The launch itself is not wrapped: only the buffers the caller hands over
+//! are dumped, and only `f32` buffers are accepted. This
+//! mirrors ZLUDA's "dump kernel arguments before and after launch" approach
+//! and gives callers a deterministic record to diff correctness regressions
+//! against, or to replay a single invocation outside the full program.
+
+use crate::TritonMetadata;
+use cudarc::driver::{CudaDevice, CudaSlice, DeviceRepr, DriverError, LaunchConfig, ValidAsZeroBits};
+use serde_derive::Serialize;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Env var that turns on argument dumping when set to any value other than
+/// `"0"` or the empty string.
+pub static DUMP_ENV_VAR: &str = "TRITON_RS_DUMP_ARGS";
+
+/// Where dumped argument files and manifests are written. Defaults to
+/// `./triton_dumps` when [`DUMP_ENV_VAR`] is set but `TRITON_RS_DUMP_DIR` is
+/// not.
+fn dump_dir() -> PathBuf {
+    match env::var("TRITON_RS_DUMP_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => PathBuf::from("triton_dumps"),
+    }
+}
+
+/// Returns `true` if argument dumping is enabled via [`DUMP_ENV_VAR`].
+pub fn dump_enabled() -> bool {
+    match env::var(DUMP_ENV_VAR) {
+        Ok(val) => val != "0" && !val.is_empty(),
+        Err(_) => false,
+    }
+}
+
+#[derive(Serialize)]
+struct LaunchManifest<'a> {
+    kernel: &'a str,
+    launch: u64,
+    grid_dim: (u32, u32, u32),
+    block_dim: (u32, u32, u32),
+    shared_mem_bytes: u32,
+    metadata_name: &'a str,
+}
+
+#[derive(Serialize)]
+struct ArgManifest {
+    index: usize,
+    dtype: &'static str,
+    len: usize,
+}
+
+/// Copies a single device buffer back to the host and writes it to
+/// `<dir>/<kernel>.<launch>.arg<index>.<phase>`, alongside a small JSON
+/// sidecar recording its dtype and length.
+fn dump_arg<T: DeviceRepr + ValidAsZeroBits + Default + Clone + serde::Serialize>(
+    dev: &CudaDevice,
+    dir: &Path,
+    kernel: &str,
+    launch: u64,
+    index: usize,
+    phase: &str,
+    slice: &CudaSlice<T>,
+) -> Result<(), DriverError> {
+    let host: Vec<T> = dev.dtoh_sync_copy(slice)?;
+
+    let data_path = dir.join(format!("{kernel}.{launch}.arg{index}.{phase}"));
+    let json = serde_json::to_vec(&host).expect("serialize dumped argument");
+    File::create(&data_path)
+        .and_then(|mut f| f.write_all(&json))
+        .unwrap_or_else(|e| panic!("failed to write {:?}: {e}", data_path));
+
+    let manifest = ArgManifest {
+        index,
+        dtype: std::any::type_name::<T>(),
+        len: host.len(),
+    };
+    let manifest_path = dir.join(format!("{kernel}.{launch}.arg{index}.{phase}.json"));
+    let manifest_json = serde_json::to_vec_pretty(&manifest).expect("serialize arg manifest");
+    File::create(&manifest_path)
+        .and_then(|mut f| f.write_all(&manifest_json))
+        .unwrap_or_else(|e| panic!("failed to write {:?}: {e}", manifest_path));
+
+    Ok(())
+}
+
+/// Writes the per-launch manifest (grid/block dims, shared memory, and the
+/// `TritonMetadata` name) once per launch, not once per argument.
+fn dump_launch_manifest(
+    dir: &Path,
+    kernel: &str,
+    launch: u64,
+    cfg: &LaunchConfig,
+    metadata: &TritonMetadata,
+) {
+    let manifest = LaunchManifest {
+        kernel,
+        launch,
+        grid_dim: cfg.grid_dim,
+        block_dim: cfg.block_dim,
+        shared_mem_bytes: cfg.shared_mem_bytes,
+        metadata_name: &metadata.name,
+    };
+    let path = dir.join(format!("{kernel}.{launch}.json"));
+    let json = serde_json::to_vec_pretty(&manifest).expect("serialize launch manifest");
+    File::create(&path)
+        .and_then(|mut f| f.write_all(&json))
+        .unwrap_or_else(|e| panic!("failed to write {:?}: {e}", path));
+}
+
+/// Dumps `f32` argument buffers and the launch manifest before a launch.
+/// `args` must include every buffer the kernel touches, output included —
+/// omitting the output buffer makes `.pre` and `.post` identical for any
+/// kernel whose inputs are read-only, defeating the point of the dump.
+/// Accepts any number of buffers, but only `f32` ones; scalar arguments
+/// (such as `add_kernel`'s `n`) are not dumped.
+pub fn dump_pre(
+    dev: &CudaDevice,
+    kernel: &str,
+    launch: u64,
+    cfg: &LaunchConfig,
+    metadata: &TritonMetadata,
+    args: &[&CudaSlice<f32>],
+) -> Result<(), DriverError> {
+    let dir = dump_dir();
+    std::fs::create_dir_all(&dir).expect("create dump dir");
+    dump_launch_manifest(&dir, kernel, launch, cfg, metadata);
+    for (index, slice) in args.iter().enumerate() {
+        dump_arg(dev, &dir, kernel, launch, index, "pre", slice)?;
+    }
+    Ok(())
+}
+
+/// Dumps the same argument set (inputs and output) after the launch has
+/// completed.
+pub fn dump_post(
+    dev: &CudaDevice,
+    kernel: &str,
+    launch: u64,
+    args: &[&CudaSlice<f32>],
+) -> Result<(), DriverError> {
+    let dir = dump_dir();
+    for (index, slice) in args.iter().enumerate() {
+        dump_arg(dev, &dir, kernel, launch, index, "post", slice)?;
+    }
+    Ok(())
+}
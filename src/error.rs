@@ -0,0 +1,104 @@
+//! Structured errors for `TritonKernel`'s load path, so callers get an
+//! actionable message instead of an opaque driver failure or a bare assert
+//! panic.
+
+use crate::BackendError;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A `(major, minor)` SM compute capability, e.g. `(8, 0)` for `sm_80`.
+pub type ComputeCapability = (u32, u32);
+
+#[derive(Debug)]
+pub enum TritonError {
+    /// The artifact was compiled for a compute capability the selected
+    /// device does not support.
+    ArchMismatch {
+        required: ComputeCapability,
+        available: ComputeCapability,
+    },
+    /// `metadata.shared` exceeds the device's max shared memory per block.
+    SharedMemoryExceeded { requested: u32, max_available: u32 },
+    /// The artifact's PTX ISA version needs a newer CUDA driver than the one
+    /// installed on this host.
+    DriverTooOld {
+        required: (u32, u32),
+        available: (u32, u32),
+    },
+    /// The artifact requests a thread block cluster launch (`cluster_dims`)
+    /// or persistent scheduling (`enable_persistent`), both of which need
+    /// sm_90+.
+    ClusterUnsupported { available: ComputeCapability },
+    /// The kernel's `target` metadata names a backend this load path can't
+    /// dispatch to: [`crate::Backend::Hip`] (no HIP runtime is linked in) or
+    /// a target that isn't recognized at all.
+    Backend(BackendError),
+    /// No cache entry matched the kernel name / query.
+    NotFound(String),
+    /// More than one cache entry matched and the query didn't disambiguate
+    /// between them.
+    Ambiguous(Vec<PathBuf>),
+    Io(String),
+    Parse(String),
+    Driver(cudarc::driver::DriverError),
+}
+
+impl fmt::Display for TritonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TritonError::ArchMismatch {
+                required,
+                available,
+            } => write!(
+                f,
+                "kernel requires sm_{}{}, but device only supports sm_{}{}",
+                required.0, required.1, available.0, available.1
+            ),
+            TritonError::SharedMemoryExceeded {
+                requested,
+                max_available,
+            } => write!(
+                f,
+                "kernel requests {requested} bytes of shared memory, device allows at most {max_available}"
+            ),
+            TritonError::DriverTooOld {
+                required,
+                available,
+            } => write!(
+                f,
+                "kernel's PTX requires CUDA {}.{} or newer, but the installed driver only supports {}.{}",
+                required.0, required.1, available.0, available.1
+            ),
+            TritonError::ClusterUnsupported { available } => write!(
+                f,
+                "kernel requires thread block clusters or persistent scheduling (sm_90+), but device is sm_{}{}",
+                available.0, available.1
+            ),
+            TritonError::Backend(e) => write!(f, "{e}"),
+            TritonError::NotFound(name) => write!(f, "no cached artifact found for kernel `{name}`"),
+            TritonError::Ambiguous(paths) => write!(
+                f,
+                "{} cache entries matched, expected exactly one: {:?}",
+                paths.len(),
+                paths
+            ),
+            TritonError::Io(msg) => write!(f, "{msg}"),
+            TritonError::Parse(msg) => write!(f, "{msg}"),
+            TritonError::Driver(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TritonError {}
+
+impl From<cudarc::driver::DriverError> for TritonError {
+    fn from(e: cudarc::driver::DriverError) -> Self {
+        TritonError::Driver(e)
+    }
+}
+
+impl From<BackendError> for TritonError {
+    fn from(e: BackendError) -> Self {
+        TritonError::Backend(e)
+    }
+}
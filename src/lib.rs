@@ -1,4 +1,3 @@
-use cudarc::driver::sys;
 use cudarc::{
     driver::{CudaDevice, CudaSlice, DriverError, LaunchAsync, LaunchConfig},
     nvrtc::Ptx,
@@ -9,7 +8,25 @@ use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs::File;
 use std::io::Read;
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+mod async_launch;
+pub use async_launch::{launch_async, LaunchHandle};
+
+mod dump;
+pub use dump::{dump_enabled, dump_post, dump_pre, DUMP_ENV_VAR};
+
+mod backend;
+pub use backend::{Backend, BackendError, CudaBackend, DeviceBackend, HipBackend};
+
+mod error;
+pub use error::{ComputeCapability, TritonError};
+
+mod validate;
+pub use validate::{device_compute_capability, device_max_shared_mem_per_block};
 
 lazy_static! {
     static ref TRITON_CACHE: String = {
@@ -24,6 +41,14 @@ lazy_static! {
 }
 
 static KERNEL_NAME: &str = "add_kernel_0d1d2d3de";
+/// The `BLOCK_SIZE` constexpr the `add_kernel` test fixture was compiled
+/// with (Triton's vector-add tutorial kernel). `TritonMetadata` doesn't
+/// carry compiled constexpr values, so this can't be read back out of the
+/// metadata; it must match whatever `BLOCK_SIZE` the fixture was compiled
+/// with. This is the *tile* each program processes, not `num_warps * 32`
+/// (the hardware thread count per block) — see
+/// [`TritonMetadata::launch_config_for_n`].
+static ADD_KERNEL_BLOCK_SIZE: u32 = 1024;
 pub struct TritonKernel {
     pub name: String,
 }
@@ -34,17 +59,53 @@ impl TritonKernel {
             name: name.to_string(),
         }
     }
-    pub fn metadata(&self) -> TritonMetadata {
+    /// Loads this kernel's metadata, failing if the cache holds zero or more
+    /// than one matching compilation. Use [`TritonKernel::select`] to
+    /// disambiguate when the cache legitimately holds several variants
+    /// (different dtypes, block sizes, or archs).
+    pub fn metadata(&self) -> Result<TritonMetadata, TritonError> {
         let paths = find_ext(&self.name, "json");
-        assert!(paths.len() == 1);
-        let mut file = File::open(&paths[0]).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        let metadata: TritonMetadata = match serde_json::from_str(&contents) {
-            Ok(v) => v,
-            Err(e) => panic!("{:?}", e),
-        };
-        metadata
+        match paths.len() {
+            0 => Err(TritonError::NotFound(self.name.clone())),
+            1 => read_metadata(&paths[0]),
+            _ => Err(TritonError::Ambiguous(paths)),
+        }
+    }
+    /// Enumerates every compiled artifact for this kernel name under the
+    /// Triton cache and returns the one matching `query`, disambiguating
+    /// multiple cache hits instead of asserting there is exactly one.
+    pub fn select(&self, query: ArtifactQuery) -> Result<(PathBuf, PathBuf, TritonMetadata), TritonError> {
+        let mut matches = Vec::new();
+        for json_path in find_ext(&self.name, "json") {
+            // A stale or mid-write sibling from another compilation
+            // shouldn't take down every query against this kernel name, so
+            // unreadable entries are skipped rather than propagated.
+            let Ok(metadata) = read_metadata(&json_path) else {
+                continue;
+            };
+            if query.matches(&metadata) {
+                matches.push((json_path, metadata));
+            }
+        }
+
+        match matches.len() {
+            0 => Err(TritonError::NotFound(self.name.clone())),
+            1 => {
+                let (json_path, metadata) = matches.remove(0);
+                let cubin_path = json_path.with_extension("cubin");
+                let ptx_path = json_path.with_extension("ptx");
+                // A HIP-only entry compiles to `.hsaco`, not `.cubin`/`.ptx`;
+                // rather than hand back a path that only fails later at
+                // load, report it as not found here.
+                if !cubin_path.exists() || !ptx_path.exists() {
+                    return Err(TritonError::NotFound(self.name.clone()));
+                }
+                Ok((cubin_path, ptx_path, metadata))
+            }
+            _ => Err(TritonError::Ambiguous(
+                matches.into_iter().map(|(path, _)| path).collect(),
+            )),
+        }
     }
     pub fn cubin(&self) -> Vec<PathBuf> {
         find_ext(&self.name, "cubin")
@@ -52,6 +113,81 @@ impl TritonKernel {
     pub fn ptx(&self) -> Vec<PathBuf> {
         find_ext(&self.name, "ptx")
     }
+    pub fn hsaco(&self) -> Vec<PathBuf> {
+        find_ext(&self.name, "hsaco")
+    }
+    /// The device runtime this kernel was compiled for, per its metadata's
+    /// `target`. `None` if the metadata couldn't be loaded unambiguously or
+    /// the target string isn't recognized.
+    pub fn backend(&self) -> Option<Backend> {
+        Backend::detect(&self.metadata().ok()?.target())
+    }
+    /// Validates that `dev` can run this kernel's artifact, then loads it.
+    /// Dispatches on the artifact's `target` metadata: only `Backend::Cuda`
+    /// is wired to an actual load path today, so a `Backend::Hip` (or
+    /// unrecognized) target fails fast with [`TritonError::Backend`]
+    /// instead of silently falling through to a CUDA-only load that would
+    /// fail opaquely (or load the wrong artifact) further down.
+    pub fn load_ptx_checked(
+        &self,
+        dev: &CudaDevice,
+        module_name: &str,
+        kernel_name: &str,
+    ) -> Result<(), TritonError> {
+        let metadata = self.metadata()?;
+        match Backend::detect(&metadata.target()) {
+            Some(Backend::Cuda) => {}
+            Some(Backend::Hip) => {
+                return Err(BackendError::Unsupported("HIP runtime not linked in".into()).into());
+            }
+            None => {
+                return Err(BackendError::Unsupported(format!(
+                    "unrecognized target `{}`",
+                    metadata.target()
+                ))
+                .into());
+            }
+        }
+        metadata.check_device(dev)?;
+        let cubin_path = self
+            .cubin()
+            .into_iter()
+            .next()
+            .ok_or_else(|| TritonError::NotFound(self.name.clone()))?;
+        dev.load_ptx(Ptx::from_file(&cubin_path), module_name, &[kernel_name])
+            .map_err(TritonError::from)
+    }
+}
+
+/// Filters over [`TritonMetadata`] fields used to pick one compiled
+/// artifact out of several cache hits for the same kernel name.
+#[derive(Debug, Default, Clone)]
+pub struct ArtifactQuery<'a> {
+    pub target: Option<&'a str>,
+    pub num_warps: Option<u32>,
+    pub num_stages: Option<u32>,
+    /// Matches if `TritonMetadata::name` ends with this specialization
+    /// suffix (e.g. the `_0d1d2d3de` signature suffix Triton appends).
+    pub name_suffix: Option<&'a str>,
+}
+
+impl<'a> ArtifactQuery<'a> {
+    fn matches(&self, metadata: &TritonMetadata) -> bool {
+        self.target.map_or(true, |t| metadata.target() == t)
+            && self.num_warps.map_or(true, |w| metadata.num_warps == w)
+            && self.num_stages.map_or(true, |s| metadata.num_stages == s)
+            && self
+                .name_suffix
+                .map_or(true, |suffix| metadata.name.ends_with(suffix))
+    }
+}
+
+fn read_metadata(path: &Path) -> Result<TritonMetadata, TritonError> {
+    let mut file = File::open(path).map_err(|e| TritonError::Io(e.to_string()))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(|e| TritonError::Io(e.to_string()))?;
+    serde_json::from_str(&contents).map_err(|e| TritonError::Parse(e.to_string()))
 }
 pub fn find_ext(kernel_name: &str, ext: &str) -> Vec<PathBuf> {
     let mut found_paths: Vec<PathBuf> = Vec::new();
@@ -107,6 +243,58 @@ impl TritonMetadata {
             .join("")
             .replace('\"', "")
     }
+    /// Builds a `LaunchConfig` for this kernel: block size from `num_warps`,
+    /// shared memory from `shared`, and the caller-supplied grid. Use this
+    /// instead of hand-writing a `LaunchConfig`, which otherwise silently
+    /// drops the kernel's actual shared-memory requirement.
+    pub fn launch_config(&self, grid: (u32, u32, u32)) -> TritonLaunchConfig {
+        TritonLaunchConfig {
+            cfg: LaunchConfig {
+                block_dim: (self.num_warps * 32, 1, 1),
+                grid_dim: grid,
+                shared_mem_bytes: self.shared,
+            },
+            cluster_dims: self.nontrivial_cluster_dims(),
+        }
+    }
+    /// Like [`TritonMetadata::launch_config`], but derives the grid from a
+    /// total element count and the kernel's block size, the way Triton's own
+    /// grid lambdas do: `grid = ceil(n / block_size)`. `block_size` is
+    /// floored to 1 instead of panicking on division by zero.
+    ///
+    /// `block_size` must be the kernel's `BLOCK_SIZE` constexpr tile (the
+    /// number of elements each program instance processes), **not**
+    /// `num_warps * 32` (the hardware thread count per block, used for
+    /// [`TritonMetadata::launch_config`]'s `block_dim`). `TritonMetadata`
+    /// doesn't capture compiled constexpr values, so the caller must supply
+    /// the tile size it compiled the kernel with; passing the thread count
+    /// instead computes a grid sized for the wrong unit and, for any tile
+    /// larger than `num_warps * 32`, launches more programs than the kernel
+    /// needs.
+    pub fn launch_config_for_n(&self, n: u32, block_size: u32) -> TritonLaunchConfig {
+        let block_size = block_size.max(1);
+        let grid = n / block_size + u32::from(n % block_size != 0);
+        self.launch_config((grid, 1, 1))
+    }
+    /// `cluster_dims`, if it requests anything other than the trivial `(1,
+    /// 1, 1)` cluster.
+    fn nontrivial_cluster_dims(&self) -> Option<(u32, u32, u32)> {
+        match self.cluster_dims.as_slice() {
+            [x, y, z] if !(*x == 1 && *y == 1 && *z == 1) => Some((*x, *y, *z)),
+            _ => None,
+        }
+    }
+}
+
+/// A `LaunchConfig` plus any cluster launch dimensions Triton requested.
+/// cudarc's `LaunchConfig`/`LaunchAsync::launch` don't support cluster
+/// launches (that needs `cuLaunchKernelEx`), so a caller that gets back
+/// `cluster_dims: Some(_)` needs to issue that call itself rather than
+/// going through the plain `cfg` — the dims are surfaced here instead of
+/// being silently dropped.
+pub struct TritonLaunchConfig {
+    pub cfg: LaunchConfig,
+    pub cluster_dims: Option<(u32, u32, u32)>,
 }
 #[cfg(test)]
 mod tests {
@@ -116,15 +304,15 @@ mod tests {
     fn test_load() {
         let kernel = TritonKernel::new("add_kernel");
         let paths = kernel.cubin();
-        assert!(paths.len() == 1);
+        assert!(!paths.is_empty());
         let paths = kernel.ptx();
-        assert!(paths.len() == 1);
+        assert!(!paths.is_empty());
     }
 
     #[test]
     fn test_metadata() {
         let kernel = TritonKernel::new("add_kernel");
-        let metadata = kernel.metadata();
+        let metadata = kernel.metadata().unwrap();
         assert!(metadata.name.contains(format!("{}", kernel.name).as_str()));
     }
 
@@ -134,8 +322,7 @@ mod tests {
         // You can load a function from a pre-compiled PTX like so:
         let ptx_path = &kernel.ptx()[0];
         let cubin_path = &kernel.cubin()[0];
-        let metadata = kernel.metadata();
-        let num_threads = metadata.num_warps * 32;
+        let metadata = kernel.metadata().unwrap();
         // let func_name: String = metadata.name.clone();
         let module_name = "triton";
 
@@ -156,16 +343,92 @@ mod tests {
         let mut c_dev: CudaSlice<f32> = dev.alloc_zeros(a_host.len()).unwrap();
 
         let n = a_host.len() as u32;
-        let cfg = LaunchConfig {
-            block_dim: (1, 1, 1),
-            grid_dim: (num_threads, 1, 1),
-            shared_mem_bytes: 0,
-        };
+        let cfg = metadata.launch_config_for_n(n, ADD_KERNEL_BLOCK_SIZE).cfg;
+        if dump_enabled() {
+            dump_pre(&dev, &kernel.name, 0, &cfg, &metadata, &[&a_dev, &b_dev, &c_dev])?;
+        }
         unsafe { f.launch(cfg, (&a_dev, &b_dev, &mut c_dev, n)) }?;
+        if dump_enabled() {
+            dump_post(&dev, &kernel.name, 0, &[&a_dev, &b_dev, &c_dev])?;
+        }
 
         let c_host = dev.sync_reclaim(c_dev)?;
         assert!(c_host == expected);
 
         Ok(())
     }
+
+    #[test]
+    fn test_kernel_async() -> Result<(), DriverError> {
+        let kernel = TritonKernel::new("add_kernel");
+        let cubin_path = &kernel.cubin()[0];
+        let metadata = kernel.metadata().unwrap();
+        let module_name = "triton";
+
+        let dev = CudaDevice::new(0)?;
+        dev.load_ptx(Ptx::from_file(cubin_path), module_name, &[KERNEL_NAME])?;
+        let f = dev.get_func(module_name, KERNEL_NAME).unwrap();
+        let stream = dev.fork_default_stream()?;
+
+        let a_host: [f32; 3] = [1.0, 2.0, 3.0];
+        let b_host: [f32; 3] = [4.0, 5.0, 6.0];
+        let expected = a_host
+            .into_iter()
+            .zip(b_host)
+            .map(|(a, b)| a + b)
+            .collect::<Vec<_>>();
+
+        let n = a_host.len() as u32;
+        let cfg = metadata.launch_config_for_n(n, ADD_KERNEL_BLOCK_SIZE).cfg;
+
+        let handle = launch_async(&dev, &stream, f, cfg, &a_host, &b_host)?;
+        let c_host = handle.synchronize()?;
+        assert!(c_host == expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch_config_for_n() {
+        let kernel = TritonKernel::new("add_kernel");
+        let metadata = kernel.metadata().unwrap();
+
+        let launch_cfg = metadata.launch_config_for_n(257, 128);
+        assert_eq!(launch_cfg.cfg.grid_dim, (3, 1, 1));
+        assert_eq!(launch_cfg.cfg.block_dim, (metadata.num_warps * 32, 1, 1));
+        assert_eq!(launch_cfg.cfg.shared_mem_bytes, metadata.shared);
+    }
+
+    #[test]
+    fn test_launch_config_for_n_zero_block_size() {
+        let kernel = TritonKernel::new("add_kernel");
+        let metadata = kernel.metadata().unwrap();
+
+        let launch_cfg = metadata.launch_config_for_n(3, 0);
+        assert_eq!(launch_cfg.cfg.grid_dim, (3, 1, 1));
+    }
+
+    #[test]
+    fn test_select_disambiguates_by_query() {
+        let kernel = TritonKernel::new("add_kernel");
+        let metadata = kernel.metadata().unwrap();
+
+        let (cubin_path, ptx_path, selected) = kernel
+            .select(ArtifactQuery {
+                num_warps: Some(metadata.num_warps),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(cubin_path.ends_with("add_kernel.cubin"));
+        assert!(ptx_path.ends_with("add_kernel.ptx"));
+        assert_eq!(selected.num_warps, metadata.num_warps);
+
+        let err = kernel
+            .select(ArtifactQuery {
+                num_warps: Some(metadata.num_warps + 1000),
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(matches!(err, TritonError::NotFound(_)));
+    }
 }
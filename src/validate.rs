@@ -0,0 +1,123 @@
+//! Load-time validation of a `TritonKernel` artifact against the device it
+//! is about to be loaded on.
+
+use crate::{Backend, TritonError, TritonMetadata};
+use cudarc::driver::{sys, CudaDevice};
+
+/// Reads `sm_<major><minor>` off the live device via
+/// `cuDeviceGetAttribute`.
+pub fn device_compute_capability(dev: &CudaDevice) -> Result<(u32, u32), TritonError> {
+    let major = unsafe {
+        sys::lib()
+            .cuDeviceGetAttribute(
+                sys::CUdevice_attribute_enum::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR,
+                dev.cu_device(),
+            )
+            .result()?
+    };
+    let minor = unsafe {
+        sys::lib()
+            .cuDeviceGetAttribute(
+                sys::CUdevice_attribute_enum::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR,
+                dev.cu_device(),
+            )
+            .result()?
+    };
+    Ok((major as u32, minor as u32))
+}
+
+/// Reads the device's max shared memory per block, in bytes, available via
+/// the opt-in dynamic shared memory path
+/// (`CU_DEVICE_ATTRIBUTE_MAX_SHARED_MEMORY_PER_BLOCK_OPTIN`). The plain
+/// `..._MAX_SHARED_MEMORY_PER_BLOCK` attribute only reports the static ~48
+/// KB limit and would falsely reject any kernel that opts into the larger
+/// dynamic allocation, which is the common case for Triton kernels with a
+/// sizeable `shared`.
+pub fn device_max_shared_mem_per_block(dev: &CudaDevice) -> Result<u32, TritonError> {
+    let bytes = unsafe {
+        sys::lib()
+            .cuDeviceGetAttribute(
+                sys::CUdevice_attribute_enum::CU_DEVICE_ATTRIBUTE_MAX_SHARED_MEMORY_PER_BLOCK_OPTIN,
+                dev.cu_device(),
+            )
+            .result()?
+    };
+    Ok(bytes as u32)
+}
+
+/// Reads the CUDA version the installed driver supports, as `(major,
+/// minor)`, via `cuDriverGetVersion`.
+pub fn driver_cuda_version() -> Result<(u32, u32), TritonError> {
+    let version = unsafe { sys::lib().cuDriverGetVersion().result()? };
+    Ok((version as u32 / 1000, (version as u32 % 1000) / 10))
+}
+
+/// The minimum CUDA driver version that can load PTX at `ptx_version`
+/// (encoded as `<ISA major><ISA minor>`, e.g. `83` for PTX ISA 8.3). PTX ISA
+/// major versions track `CUDA major - 4` (PTX ISA 7.x shipped with CUDA
+/// 11.x, 8.x with CUDA 12.x).
+fn required_driver_version(ptx_version: u32) -> (u32, u32) {
+    (ptx_version / 10 + 4, ptx_version % 10)
+}
+
+impl TritonMetadata {
+    /// The `(major, minor)` SM compute capability this artifact was
+    /// compiled for, parsed from `target`. `None` for non-CUDA targets
+    /// (e.g. `hip`, whose arch is a gfx name rather than an SM number) or
+    /// if the target's shape doesn't match what we expect.
+    pub fn required_compute_capability(&self) -> Option<(u32, u32)> {
+        if Backend::detect(&self.target()) != Some(Backend::Cuda) {
+            return None;
+        }
+        let arch = self.target.get(1)?.as_u64()?;
+        Some((arch as u32 / 10, arch as u32 % 10))
+    }
+
+    /// Validates that `dev` can actually run this artifact: its compute
+    /// capability must be at least what the artifact was compiled for, the
+    /// installed driver must support the artifact's PTX ISA version, its max
+    /// shared memory per block must be at least `self.shared`, and any
+    /// thread block cluster or persistent-scheduling request must be
+    /// satisfiable (both need sm_90+).
+    pub fn check_device(&self, dev: &CudaDevice) -> Result<(), TritonError> {
+        let compute_capability = device_compute_capability(dev)?;
+
+        if let Some(required) = self.required_compute_capability() {
+            if compute_capability < required {
+                return Err(TritonError::ArchMismatch {
+                    required,
+                    available: compute_capability,
+                });
+            }
+        }
+
+        if let Some(ptx_version) = self.ptx_version {
+            let required = required_driver_version(ptx_version);
+            let available = driver_cuda_version()?;
+            if available < required {
+                return Err(TritonError::DriverTooOld {
+                    required,
+                    available,
+                });
+            }
+        }
+
+        let max_shared = device_max_shared_mem_per_block(dev)?;
+        if self.shared > max_shared {
+            return Err(TritonError::SharedMemoryExceeded {
+                requested: self.shared,
+                max_available: max_shared,
+            });
+        }
+
+        if (self.nontrivial_cluster_dims().is_some() || self.enable_persistent)
+            && compute_capability < (9, 0)
+        {
+            return Err(TritonError::ClusterUnsupported {
+                available: compute_capability,
+            });
+        }
+
+        Ok(())
+    }
+}